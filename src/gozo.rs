@@ -2,39 +2,224 @@
 //  License, v. 2.0. If a copy of the MPL was not distributed with this
 //  file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
-use async_nats::{ jetstream::kv, Client, HeaderMap, HeaderValue, Message };
-use futures::stream::TryStreamExt;
+use crate::storage::Storage;
+use async_nats::{ Client, HeaderMap, HeaderValue, Message };
 use serde::{ Serialize, Deserialize };
-use std::collections::BTreeMap;
+use std::cmp::Ordering;
+use std::collections::{ BinaryHeap, HashMap, HashSet };
 use std::str::FromStr;
 use std::sync::Arc;
 use std::time::{ Duration, SystemTime };
-use tokio::sync::{ Mutex, MutexGuard };
+use tokio::sync::{ Mutex, MutexGuard, Notify };
 
 #[derive(Clone, Serialize, Deserialize)]
 pub struct Entry {
 	when: u64,
 	subject: String,
 	payload: bytes::Bytes,
+	cron: Option<String>,
+}
+
+// Schema 1 is this `Entry` shape, stored as a leading version byte followed
+// by its `rmp_serde` encoding. Bumping `when` from seconds to milliseconds
+// (and adding `cron`) needs this marker so `decode_entry` can tell a fresh
+// record apart from one written by a pre-schema build, which had neither
+// and would otherwise get misread as milliseconds and fire immediately.
+const SCHEMA_VERSION: u8 = 1;
+
+// Pre-schema on-disk shape: `when` was seconds and there was no `cron`.
+#[derive(Deserialize)]
+struct LegacyEntry {
+	when: u64,
+	subject: String,
+	payload: bytes::Bytes,
+}
+
+fn encode_entry(entry: &Entry) -> bytes::Bytes {
+	let mut buf = vec![SCHEMA_VERSION];
+	buf.extend(rmp_serde::to_vec_named(entry).unwrap());
+	buf.into()
+}
+
+// Decodes either shape, converting a legacy (pre-schema, seconds-granularity)
+// entry to the current one; rewriting it in storage is up to the caller.
+fn decode_entry(bytes: &[u8]) -> Entry {
+	match bytes.split_first() {
+		Some((&version, rest)) if version == SCHEMA_VERSION => {
+			rmp_serde::from_slice(rest).unwrap()
+		},
+		_ => {
+			let legacy: LegacyEntry = rmp_serde::from_slice(bytes).unwrap();
+			Entry {
+				when: legacy.when * 1000,
+				subject: legacy.subject,
+				payload: legacy.payload,
+				cron: None,
+			}
+		},
+	}
+}
+
+// Ordered by `when` ascending (and `seq` to break ties), reversed so that
+// `BinaryHeap`, which is a max-heap, surfaces the earliest deadline first.
+struct HeapItem {
+	when: u64,
+	seq: u64,
+	id: Option<String>,
+	entry: Entry,
+}
+
+impl PartialEq for HeapItem {
+	fn eq(&self, other: &Self) -> bool {
+		self.when == other.when && self.seq == other.seq
+	}
+}
+
+impl Eq for HeapItem {}
+
+impl Ord for HeapItem {
+	fn cmp(&self, other: &Self) -> Ordering {
+		other.when.cmp(&self.when).then_with(|| other.seq.cmp(&self.seq))
+	}
+}
+
+impl PartialOrd for HeapItem {
+	fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+		Some(self.cmp(other))
+	}
 }
 
-#[derive(Clone)]
 pub struct Sched {
-	pub entry: Vec<Entry>,
-	pub id: Vec<Option<String>>,
-	pub id_loc: BTreeMap<String, usize>,
+	heap: BinaryHeap<HeapItem>,
+	// Sequence number of the entry currently pending for a given id. A
+	// `Gozo-Id` upsert bumps this to implicitly invalidate the entry it
+	// replaces, and `Gozo-Del-Id` removes it outright; either way a popped
+	// heap item whose `seq` no longer matches is stale and gets dropped
+	// instead of published. This is what makes cancellation O(1) against a
+	// heap that can't otherwise remove an arbitrary entry cheaply.
+	valid: HashMap<String, u64>,
+	// Ids popped by `drain_due` whose reschedule decision (publish, then
+	// for `cron` entries push a continuation) hasn't been finalized via
+	// `retire` yet. An id is removed from `valid` the moment it's drained,
+	// so without this a `Gozo-Del-Id` arriving during the publish
+	// round-trip would find nothing to cancel and `retire` would push the
+	// continuation anyway, silently un-cancelling a recurring job. `cancel`
+	// clears an id from here too, and `retire` only reschedules if its id
+	// is still present.
+	in_flight: HashSet<String>,
+	next_seq: u64,
+	pub notify: Arc<Notify>,
 }
 
 impl Sched {
 	pub fn new() -> Sched {
 		Sched {
-			entry: Vec::new(),
-			id: Vec::new(),
-			id_loc: BTreeMap::new(),
+			heap: BinaryHeap::new(),
+			valid: HashMap::new(),
+			in_flight: HashSet::new(),
+			next_seq: 0,
+			notify: Arc::new(Notify::new()),
+		}
+	}
+
+	fn push(&mut self, id: Option<String>, entry: Entry) {
+		let seq = self.next_seq;
+		self.next_seq += 1;
+
+		if let Some(id) = &id {
+			self.valid.insert(id.clone(), seq);
 		}
+
+		self.heap.push(HeapItem { when: entry.when, seq, id, entry });
+		self.notify.notify_one();
+	}
+
+	fn cancel(&mut self, id: &str) {
+		self.valid.remove(id);
+		self.in_flight.remove(id);
+	}
+
+	// Pops every entry due at or before `now`, dropping (and not returning)
+	// any that a `Gozo-Id` upsert or `Gozo-Del-Id` made stale in the
+	// meantime. Kept separate from `replyloop` so the scheduling logic is
+	// testable without a live NATS connection.
+	fn drain_due(&mut self, now: u64) -> Vec<(Option<String>, Entry)> {
+		let mut due = Vec::new();
+
+		while let Some(item) = self.heap.peek() {
+			if item.when > now {
+				break;
+			}
+
+			let HeapItem { seq, id, entry, .. } = self.heap.pop().unwrap();
+			if let Some(id) = &id {
+				if self.valid.get(id) != Some(&seq) {
+					continue;
+				}
+				self.valid.remove(id);
+				self.in_flight.insert(id.clone());
+			}
+
+			due.push((id, entry));
+		}
+
+		due
+	}
+
+	// Finalizes a `drain_due`'d entry once its publish has completed.
+	// `next`, if given, is the cron continuation to push. The continuation
+	// is only pushed if `id` is still marked `in_flight` — a `cancel` that
+	// arrived during the publish round-trip removes it from there, so a
+	// `Gozo-Del-Id` for a recurring job can never be silently undone by the
+	// reschedule that follows.
+	fn retire(&mut self, id: Option<String>, next: Option<Entry>) -> Retire {
+		let Some(id) = id else {
+			return Retire::Retired(None);
+		};
+
+		if self.in_flight.remove(&id) {
+			if let Some(next_entry) = next {
+				self.push(Some(id.clone()), next_entry.clone());
+				return Retire::Rescheduled(id, next_entry);
+			}
+		}
+
+		Retire::Retired(Some(id))
+	}
+
+	// Pending entries matching an introspection `QueryFilter`, ignoring
+	// ones made stale by a `Gozo-Id` upsert or `Gozo-Del-Id`.
+	fn matching(&self, filter: &QueryFilter) -> Vec<QueryEntry> {
+		self.heap.iter()
+			.filter(|item| match &item.id {
+				Some(id) => self.valid.get(id) == Some(&item.seq),
+				None => true,
+			})
+			.filter(|item| match (&item.id, &filter.id_prefix) {
+				(Some(id), Some(prefix)) => id.starts_with(prefix.as_str()),
+				(None, Some(_)) => false,
+				(_, None) => true,
+			})
+			.filter(|item| filter.when_from.map_or(true, |from| item.when >= from))
+			.filter(|item| filter.when_to.map_or(true, |to| item.when <= to))
+			.map(|item| QueryEntry {
+				id: item.id.clone(),
+				when: item.when,
+				subject: item.entry.subject.clone(),
+			})
+			.collect()
 	}
 }
 
+// Outcome of `Sched::retire`: whether a cron continuation was pushed, or
+// the job retired (one-shot, exhausted cron, or cancelled mid-flight). The
+// caller re-persists a rescheduled entry, and for a retired `Some(id)` must
+// drop the persisted KV record.
+enum Retire {
+	Rescheduled(String, Entry),
+	Retired(Option<String>),
+}
+
 pub type SchedMutex = Arc<Mutex<Sched>>;
 pub type SchedGuard<'a> = MutexGuard<'a, Sched>;
 
@@ -42,36 +227,91 @@ fn now() -> u64 {
 	SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).unwrap().as_secs()
 }
 
-pub async fn replyloop(nats: Client, kv: kv::Store, sched_mutex: SchedMutex) {
-	let mut interval = tokio::time::interval(Duration::new(1, 0));
+fn now_ms() -> u64 {
+	SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).unwrap().as_millis() as u64
+}
 
+// The next time a `Gozo-Cron` expression fires strictly after `from_ms`, or
+// `None` if the expression is invalid or has no future occurrence.
+fn next_cron_when(expr: &str, from_ms: u64) -> Option<u64> {
+	let cron: saffron::Cron = expr.parse().ok()?;
+	let from = chrono::DateTime::from_timestamp_millis(from_ms as i64)?;
+	cron.next_after(from).map(|next| next.timestamp_millis() as u64)
+}
+
+pub async fn replyloop(nats: Client, storage: Arc<dyn Storage>, sched_mutex: SchedMutex) {
 	loop {
-		interval.tick().await;
-		let mut sched = sched_mutex.lock().await;
-
-		if let Some(entry) = sched.entry.first() {
-			if entry.when <= now() {
-				let mut headers = HeaderMap::new();
-				headers.insert("Gozo-Reply", "Yes");
-				if let Some(id) = &sched.id.first().unwrap() {
-					let id = HeaderValue::from_str(id).unwrap();
-					headers.insert("Gozo-Id", id);
+		let (notify, deadline) = {
+			let sched = sched_mutex.lock().await;
+			(sched.notify.clone(), sched.heap.peek().map(|item| item.when))
+		};
+
+		match deadline {
+			Some(when) if when <= now_ms() => {},
+			Some(when) => {
+				let sleep = tokio::time::sleep_until(
+					tokio::time::Instant::now() + Duration::from_millis(when - now_ms()));
+				tokio::select! {
+					_ = sleep => {},
+					_ = notify.notified() => continue,
 				}
+			},
+			None => {
+				notify.notified().await;
+				continue;
+			},
+		}
+
+		// Drain everything due under one lock acquisition, but publish
+		// (and, for cron entries, re-schedule) after releasing it, so a
+		// burst of simultaneously-due entries doesn't hold `sched` locked
+		// across a whole run of network round-trips and starve `schedule`
+		// and `query` in the meantime.
+		let due = {
+			let mut sched = sched_mutex.lock().await;
+			sched.drain_due(now_ms())
+		};
+
+		for (id, entry) in due {
+			let mut headers = HeaderMap::new();
+			headers.insert("Gozo-Reply", "Yes");
+			if let Some(id) = &id {
+				headers.insert("Gozo-Id", HeaderValue::from_str(id).unwrap());
+			}
+
+			let _ = nats.publish_with_headers(
+				entry.subject.clone(), headers,
+				entry.payload.clone()).await;
+
+			let next = match (&id, entry.cron.as_deref()) {
+				(Some(_), Some(expr)) => next_cron_when(expr, now_ms()).map(|when| {
+					let mut next_entry = entry;
+					next_entry.when = when;
+					next_entry
+				}),
+				_ => None,
+			};
 
-				let _ = nats.publish_with_headers(
-					entry.subject.clone(), headers,
-					entry.payload.clone()).await;
+			// `retire` re-checks `in_flight`, where `drain_due` marked the id,
+			// under the lock again, so a `Gozo-Del-Id` that arrived during the
+			// publish above can still suppress this reschedule.
+			let retired = sched_mutex.lock().await.retire(id, next);
 
-				sched.entry.remove(0);
-				if let Some(id) = sched.id.remove(0) {
-					sched.id_loc.remove(&id);
-					let kv = kv.clone();
+			match retired {
+				Retire::Rescheduled(id, next_entry) => {
+					let encoded = encode_entry(&next_entry);
+					let storage = storage.clone();
 					tokio::spawn(async move {
-						let _ = kv.delete(id).await;
+						let _ = storage.put(id, encoded).await;
 					});
-				}
-
-				interval.reset_immediately();
+				},
+				Retire::Retired(Some(id)) => {
+					let storage = storage.clone();
+					tokio::spawn(async move {
+						let _ = storage.delete(id).await;
+					});
+				},
+				Retire::Retired(None) => {},
 			}
 		}
 	}
@@ -86,66 +326,271 @@ fn get_when(when: &str) -> Result<u64, std::num::ParseIntError> {
 	}
 }
 
-fn schedule_delete(sched: &mut SchedGuard<'_>, id: String) {
-	if let Some(idx) = sched.id_loc.remove(&id) {
-		sched.entry.remove(idx);
-		sched.id.remove(idx);
-	}
-}
-
-pub async fn schedule(kv: kv::Store, sched_mutex: SchedMutex, msg: Message) {
+pub async fn schedule(storage: Arc<dyn Storage>, sched_mutex: SchedMutex, msg: Message) {
 	if let Some(headers) = msg.headers {
 		if let Some(when) = headers.get("Gozo-When") {
 			if let (Ok(when), Some(reply)) = (get_when(when.as_str()), msg.reply) {
-				let mut sched = sched_mutex.lock().await;
-
-				let id = if let Some(id) = headers.get("Gozo-Id") {
-					schedule_delete(&mut sched, id.to_string());
-					Some(id.to_string())
-				} else {
-					None
+				let id = headers.get("Gozo-Id").map(|id| id.to_string());
+				let cron = match headers.get("Gozo-Cron").map(|cron| cron.to_string()) {
+					Some(_) if id.is_none() => {
+						eprintln!("gozo: ignoring Gozo-Cron sent without Gozo-Id, it would only fire once");
+						None
+					},
+					Some(cron) if cron.parse::<saffron::Cron>().is_ok() => Some(cron),
+					Some(cron) => {
+						eprintln!("gozo: ignoring invalid Gozo-Cron expression {:?}", cron);
+						None
+					},
+					None => None,
 				};
 
-				let idx = sched.entry.partition_point(|x| x.when <= when);
-
 				let entry = Entry {
-					when,
+					when: when * 1000,
 					subject: reply.to_string(),
 					payload: msg.payload,
+					cron,
 				};
 
-				sched.entry.insert(idx, entry.clone());
-				sched.id.insert(idx, id.clone());
-
-				if let Some(id) = id {
-					sched.id_loc.insert(id.clone(), idx);
-					let entry = rmp_serde::to_vec_named(&entry).unwrap();
-					let _ = kv.put(id, entry.into()).await;
+				if let Some(id) = &id {
+					let encoded = encode_entry(&entry);
+					let _ = storage.put(id.clone(), encoded).await;
 				}
+
+				let mut sched = sched_mutex.lock().await;
+				sched.push(id, entry);
 			}
 		} else if let Some(id) = headers.get("Gozo-Del-Id") {
+			let id = id.to_string();
+
 			let mut sched = sched_mutex.lock().await;
-			schedule_delete(&mut sched, id.to_string());
-			let _ = kv.delete(id).await;
+			sched.cancel(&id);
+			drop(sched);
+
+			let _ = storage.delete(id).await;
 		}
 	}
 }
 
-pub async fn schedule_load(kv: kv::Store, sched_mutex: SchedMutex)
+pub async fn schedule_load(storage: Arc<dyn Storage>, sched_mutex: SchedMutex)
 	-> Result<(), async_nats::Error> {
 
-	let mut ids = kv.keys().await?;
+	let ids = storage.keys().await?;
 	let mut sched = sched_mutex.lock().await;
 
-	while let Some(id) = ids.try_next().await? {
-		let entry = kv.get(id.clone()).await?.unwrap();
-		let entry: Entry = rmp_serde::from_slice(&entry).unwrap();
-		let idx = sched.entry.partition_point(|x| x.when <= entry.when);
+	for id in ids {
+		let bytes = storage.get(id.clone()).await?.unwrap();
+		let legacy = bytes.first() != Some(&SCHEMA_VERSION);
+		let entry = decode_entry(&bytes);
+
+		if legacy {
+			let _ = storage.put(id.clone(), encode_entry(&entry)).await;
+		}
 
-		sched.entry.insert(idx, entry);
-		sched.id.insert(idx, Some(id.clone()));
-		sched.id_loc.insert(id, idx);
+		sched.push(Some(id), entry);
 	}
 
 	Ok(())
 }
+
+/// Optional filters for a `gozo.query` request. An empty/missing payload
+/// matches everything currently pending.
+#[derive(Default, Deserialize)]
+pub struct QueryFilter {
+	id_prefix: Option<String>,
+	when_from: Option<u64>,
+	when_to: Option<u64>,
+}
+
+#[derive(Serialize)]
+pub struct QueryEntry {
+	id: Option<String>,
+	when: u64,
+	subject: String,
+}
+
+/// Answers a `gozo.query` request with the currently pending entries
+/// (ignoring ones made stale by a `Gozo-Id` upsert or `Gozo-Del-Id`),
+/// serialized with `rmp_serde` back to `msg.reply`.
+pub async fn query(nats: Client, sched_mutex: SchedMutex, msg: Message) {
+	let filter: QueryFilter = if msg.payload.is_empty() {
+		QueryFilter::default()
+	} else {
+		rmp_serde::from_slice(&msg.payload).unwrap_or_default()
+	};
+
+	let entries = sched_mutex.lock().await.matching(&filter);
+
+	if let Some(reply) = msg.reply {
+		let encoded = rmp_serde::to_vec_named(&entries).unwrap();
+		let _ = nats.publish(reply, encoded.into()).await;
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::storage::MemStorage;
+
+	fn when_msg(id: Option<&str>, when: &str, reply: &str) -> Message {
+		let mut headers = HeaderMap::new();
+		headers.insert("Gozo-When", when);
+		if let Some(id) = id {
+			headers.insert("Gozo-Id", id);
+		}
+
+		Message {
+			subject: "gozo".into(),
+			reply: Some(reply.into()),
+			payload: bytes::Bytes::from_static(b"hello"),
+			headers: Some(headers),
+			status: None,
+			description: None,
+			length: 0,
+		}
+	}
+
+	fn del_msg(id: &str) -> Message {
+		let mut headers = HeaderMap::new();
+		headers.insert("Gozo-Del-Id", id);
+
+		Message {
+			subject: "gozo".into(),
+			reply: None,
+			payload: bytes::Bytes::new(),
+			headers: Some(headers),
+			status: None,
+			description: None,
+			length: 0,
+		}
+	}
+
+	fn cron_msg(id: &str, cron: &str, reply: &str) -> Message {
+		let mut headers = HeaderMap::new();
+		headers.insert("Gozo-When", "0");
+		headers.insert("Gozo-Id", id);
+		headers.insert("Gozo-Cron", cron);
+
+		Message {
+			subject: "gozo".into(),
+			reply: Some(reply.into()),
+			payload: bytes::Bytes::from_static(b"hello"),
+			headers: Some(headers),
+			status: None,
+			description: None,
+			length: 0,
+		}
+	}
+
+	#[tokio::test]
+	async fn schedule_persists_to_storage_and_becomes_due() {
+		let storage: Arc<dyn Storage> = Arc::new(MemStorage::new());
+		let sched_mutex: SchedMutex = Arc::new(Mutex::new(Sched::new()));
+
+		schedule(storage.clone(), sched_mutex.clone(), when_msg(Some("job-1"), "0", "reply.subject")).await;
+
+		assert!(storage.get("job-1".to_string()).await.unwrap().is_some());
+
+		let due = sched_mutex.lock().await.drain_due(now_ms());
+		assert_eq!(due.len(), 1);
+		assert_eq!(due[0].0.as_deref(), Some("job-1"));
+		assert_eq!(due[0].1.subject, "reply.subject");
+	}
+
+	#[tokio::test]
+	async fn del_id_cancels_a_pending_entry_and_removes_it_from_storage() {
+		let storage: Arc<dyn Storage> = Arc::new(MemStorage::new());
+		let sched_mutex: SchedMutex = Arc::new(Mutex::new(Sched::new()));
+
+		schedule(storage.clone(), sched_mutex.clone(), when_msg(Some("job-2"), "+3600", "reply.subject")).await;
+		schedule(storage.clone(), sched_mutex.clone(), del_msg("job-2")).await;
+
+		assert!(storage.get("job-2".to_string()).await.unwrap().is_none());
+		assert!(sched_mutex.lock().await.drain_due(u64::MAX).is_empty());
+	}
+
+	#[tokio::test]
+	async fn schedule_load_restores_pending_entries_from_storage() {
+		let storage: Arc<dyn Storage> = Arc::new(MemStorage::new());
+		let sched_mutex: SchedMutex = Arc::new(Mutex::new(Sched::new()));
+
+		schedule(storage.clone(), sched_mutex.clone(), when_msg(Some("job-3"), "0", "reply.subject")).await;
+
+		let reloaded: SchedMutex = Arc::new(Mutex::new(Sched::new()));
+		schedule_load(storage.clone(), reloaded.clone()).await.unwrap();
+
+		let due = reloaded.lock().await.drain_due(now_ms());
+		assert_eq!(due.len(), 1);
+		assert_eq!(due[0].0.as_deref(), Some("job-3"));
+	}
+
+	#[tokio::test]
+	async fn retire_pushes_a_cron_continuation_when_not_cancelled() {
+		let sched_mutex: SchedMutex = Arc::new(Mutex::new(Sched::new()));
+
+		schedule(Arc::new(MemStorage::new()), sched_mutex.clone(),
+			cron_msg("job-4", "* * * * * *", "reply.subject")).await;
+
+		let due = sched_mutex.lock().await.drain_due(now_ms());
+		assert_eq!(due.len(), 1);
+		let (id, entry) = due.into_iter().next().unwrap();
+
+		let mut next_entry = entry.clone();
+		next_entry.when = now_ms() + 1000;
+		let retired = sched_mutex.lock().await.retire(id, Some(next_entry));
+
+		assert!(matches!(retired, Retire::Rescheduled(id, _) if id == "job-4"));
+		assert_eq!(sched_mutex.lock().await.drain_due(u64::MAX).len(), 1);
+	}
+
+	#[tokio::test]
+	async fn cancel_mid_flight_suppresses_the_cron_reschedule() {
+		let storage: Arc<dyn Storage> = Arc::new(MemStorage::new());
+		let sched_mutex: SchedMutex = Arc::new(Mutex::new(Sched::new()));
+
+		schedule(storage.clone(), sched_mutex.clone(),
+			cron_msg("job-5", "* * * * * *", "reply.subject")).await;
+
+		let due = sched_mutex.lock().await.drain_due(now_ms());
+		assert_eq!(due.len(), 1);
+		let (id, entry) = due.into_iter().next().unwrap();
+
+		// Simulate a `Gozo-Del-Id` arriving during the publish round-trip,
+		// after `drain_due` already removed the id from `valid`.
+		schedule(storage.clone(), sched_mutex.clone(), del_msg("job-5")).await;
+
+		let mut next_entry = entry.clone();
+		next_entry.when = now_ms() + 1000;
+		let retired = sched_mutex.lock().await.retire(id, Some(next_entry));
+
+		assert!(matches!(retired, Retire::Retired(Some(id)) if id == "job-5"));
+		assert!(sched_mutex.lock().await.drain_due(u64::MAX).is_empty());
+	}
+
+	#[tokio::test]
+	async fn matching_applies_id_prefix_and_when_range_filters() {
+		let sched_mutex: SchedMutex = Arc::new(Mutex::new(Sched::new()));
+
+		schedule(Arc::new(MemStorage::new()), sched_mutex.clone(),
+			when_msg(Some("alpha-1"), "100", "reply.alpha")).await;
+		schedule(Arc::new(MemStorage::new()), sched_mutex.clone(),
+			when_msg(Some("beta-1"), "200", "reply.beta")).await;
+
+		let sched = sched_mutex.lock().await;
+
+		let by_prefix = sched.matching(&QueryFilter {
+			id_prefix: Some("alpha".to_string()),
+			..Default::default()
+		});
+		assert_eq!(by_prefix.len(), 1);
+		assert_eq!(by_prefix[0].subject, "reply.alpha");
+
+		let by_range = sched.matching(&QueryFilter {
+			when_from: Some(150_000),
+			..Default::default()
+		});
+		assert_eq!(by_range.len(), 1);
+		assert_eq!(by_range[0].subject, "reply.beta");
+
+		assert_eq!(sched.matching(&QueryFilter::default()).len(), 2);
+	}
+}
@@ -3,10 +3,13 @@
 //  file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
 mod gozo;
+mod storage;
+mod tls;
 use argh::FromArgs;
 use async_nats::jetstream::kv;
 use futures::stream::StreamExt;
 use std::sync::Arc;
+use storage::Storage;
 use tokio::sync::Mutex;
 
 #[derive(FromArgs)]
@@ -47,6 +50,19 @@ struct Args {
 	/// set path to credentials file
 	#[argh(option, short='j')]
 	jwt: Option<String>,
+
+	/// set persistence backend: "kv" (default, JetStream) or "memory"
+	#[argh(option, short='b')]
+	storage: Option<String>,
+
+	/// add a PEM file of CA root certificates to trust (repeatable)
+	#[argh(option, short='r')]
+	ca: Vec<String>,
+
+	/// skip TLS certificate verification, for development against
+	/// self-signed NATS servers only
+	#[argh(switch, short='i')]
+	insecure_skip_verify: bool,
 }
 
 impl Args {
@@ -60,6 +76,7 @@ impl Args {
 			"key"       => self.key.clone(),
 			"nkey"      => self.nkey.clone(),
 			"jwt"       => self.jwt.clone(),
+			"storage"   => self.storage.clone(),
 			_           => None,
 		};
 
@@ -75,10 +92,27 @@ impl Args {
 		value
 	}
 
+	fn get_list(&self, field: &str) -> Vec<String> {
+		let mut value = match field {
+			"ca"        => self.ca.clone(),
+			_           => Vec::new(),
+		};
+
+		if value.is_empty() {
+			let envvar = format!("NATS_{}", field.to_uppercase());
+			if let Ok(paths) = std::env::var(envvar) {
+				value = paths.split(':').map(str::to_string).collect();
+			}
+		}
+
+		value
+	}
+
 	fn get_bool(&self, field: &str) -> bool {
 		let mut value = match field {
-			"secure"    => self.secure,
-			_           => false,
+			"secure"                => self.secure,
+			"insecure_skip_verify"  => self.insecure_skip_verify,
+			_                       => false,
 		};
 
 		if !value {
@@ -109,9 +143,10 @@ async fn main() -> Result<(), async_nats::Error> {
 		options = options.user_and_password(user, password);
 	}
 
-	if let (Some(cert), Some(key)) = (args.get("cert", None), args.get("key", None)) {
-    		options = options.add_client_certificate(cert.into(), key.into());
-    	}
+	let cert_key = match (args.get("cert", None), args.get("key", None)) {
+		(Some(cert), Some(key)) => Some((cert, key)),
+		_ => None,
+	};
 
 	if let Some(nkey) = args.get("nkey", None) {
 		options = options.nkey(nkey);
@@ -121,22 +156,51 @@ async fn main() -> Result<(), async_nats::Error> {
 		options = options.credentials_file(jwt).await?;
 	}
 
+	for ca in args.get_list("ca") {
+		options = options.add_root_certificates(ca.into());
+	}
+
+	if args.get_bool("insecure_skip_verify") {
+		// Thread the client cert/key through here rather than also calling
+		// `add_client_certificate` below, since `tls_client_config`
+		// replaces the whole rustls config wholesale.
+		options = options.tls_client_config(tls::insecure_client_config(cert_key)?);
+	} else if let (Some(cert), Some(key)) = cert_key {
+		options = options.add_client_certificate(cert.into(), key.into());
+	}
+
 	ctrlc::set_handler(|| std::process::exit(0)).ok();
 
 	let nats = async_nats::connect_with_options(address, options).await?;
-	let jetstream = async_nats::jetstream::new(nats.clone());
-	let kv = jetstream.create_key_value(kv::Config {
-		bucket: "gozo".to_string(),
-		..Default::default()
-	}).await?;
+
+	let storage: Arc<dyn Storage> = match args.get("storage", Some("kv".to_string())).unwrap().as_str() {
+		"memory" => Arc::new(storage::MemStorage::new()),
+		_ => {
+			let jetstream = async_nats::jetstream::new(nats.clone());
+			let kv = jetstream.create_key_value(kv::Config {
+				bucket: "gozo".to_string(),
+				..Default::default()
+			}).await?;
+			Arc::new(storage::KvStorage::new(kv))
+		},
+	};
 
 	let sched: gozo::SchedMutex = Arc::new(Mutex::new(gozo::Sched::new()));
-	gozo::schedule_load(kv.clone(), sched.clone()).await?;
-	tokio::spawn(gozo::replyloop(nats.clone(), kv.clone(), sched.clone()));
+	gozo::schedule_load(storage.clone(), sched.clone()).await?;
+	tokio::spawn(gozo::replyloop(nats.clone(), storage.clone(), sched.clone()));
+
+	let mut query_sub = nats.subscribe("gozo.query").await?;
+	let query_nats = nats.clone();
+	let query_sched = sched.clone();
+	tokio::spawn(async move {
+		while let Some(msg) = query_sub.next().await {
+			gozo::query(query_nats.clone(), query_sched.clone(), msg).await;
+		}
+	});
 
 	let mut sub = nats.subscribe("gozo").await?;
 	while let Some(msg) = sub.next().await {
-		gozo::schedule(kv.clone(), sched.clone(), msg).await;
+		gozo::schedule(storage.clone(), sched.clone(), msg).await;
 	}
 
 	Ok(())
@@ -0,0 +1,93 @@
+//  This Source Code Form is subject to the terms of the Mozilla Public
+//  License, v. 2.0. If a copy of the MPL was not distributed with this
+//  file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use async_nats::jetstream::kv;
+use async_trait::async_trait;
+use futures::stream::TryStreamExt;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// Durable key/value persistence for scheduled entries, abstracted away
+/// from JetStream so the scheduler can be exercised without a live NATS
+/// server and so other backends can be swapped in.
+#[async_trait]
+pub trait Storage: Send + Sync {
+	async fn put(&self, key: String, value: bytes::Bytes) -> Result<(), async_nats::Error>;
+	async fn get(&self, key: String) -> Result<Option<bytes::Bytes>, async_nats::Error>;
+	async fn delete(&self, key: String) -> Result<(), async_nats::Error>;
+	async fn keys(&self) -> Result<Vec<String>, async_nats::Error>;
+}
+
+pub struct KvStorage {
+	kv: kv::Store,
+}
+
+impl KvStorage {
+	pub fn new(kv: kv::Store) -> KvStorage {
+		KvStorage { kv }
+	}
+}
+
+#[async_trait]
+impl Storage for KvStorage {
+	async fn put(&self, key: String, value: bytes::Bytes) -> Result<(), async_nats::Error> {
+		self.kv.put(key, value).await?;
+		Ok(())
+	}
+
+	async fn get(&self, key: String) -> Result<Option<bytes::Bytes>, async_nats::Error> {
+		Ok(self.kv.get(key).await?)
+	}
+
+	async fn delete(&self, key: String) -> Result<(), async_nats::Error> {
+		self.kv.delete(key).await?;
+		Ok(())
+	}
+
+	async fn keys(&self) -> Result<Vec<String>, async_nats::Error> {
+		let mut ids = self.kv.keys().await?;
+		let mut keys = Vec::new();
+
+		while let Some(id) = ids.try_next().await? {
+			keys.push(id);
+		}
+
+		Ok(keys)
+	}
+}
+
+/// In-memory backend, mainly useful for unit-testing the scheduler or for
+/// single-node deployments that don't want to run JetStream. Entries are
+/// lost on restart.
+#[derive(Default)]
+pub struct MemStorage {
+	entries: Mutex<HashMap<String, bytes::Bytes>>,
+}
+
+impl MemStorage {
+	pub fn new() -> MemStorage {
+		MemStorage::default()
+	}
+}
+
+#[async_trait]
+impl Storage for MemStorage {
+	async fn put(&self, key: String, value: bytes::Bytes) -> Result<(), async_nats::Error> {
+		self.entries.lock().unwrap().insert(key, value);
+		Ok(())
+	}
+
+	async fn get(&self, key: String) -> Result<Option<bytes::Bytes>, async_nats::Error> {
+		Ok(self.entries.lock().unwrap().get(&key).cloned())
+	}
+
+	async fn delete(&self, key: String) -> Result<(), async_nats::Error> {
+		self.entries.lock().unwrap().remove(&key);
+		Ok(())
+	}
+
+	async fn keys(&self) -> Result<Vec<String>, async_nats::Error> {
+		Ok(self.entries.lock().unwrap().keys().cloned().collect())
+	}
+}
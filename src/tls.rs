@@ -0,0 +1,90 @@
+//  This Source Code Form is subject to the terms of the Mozilla Public
+//  License, v. 2.0. If a copy of the MPL was not distributed with this
+//  file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use rustls::client::danger::{ HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier };
+use rustls::pki_types::{ CertificateDer, PrivateKeyDer, ServerName, UnixTime };
+use rustls::{ ClientConfig, DigitallySignedStruct, SignatureScheme };
+use std::io::BufReader;
+use std::sync::Arc;
+
+/// Accepts any server certificate without verification. Only meant for
+/// connecting to self-signed NATS servers during development; never use
+/// this against a production cluster.
+#[derive(Debug)]
+struct NoCertVerification;
+
+impl ServerCertVerifier for NoCertVerification {
+	fn verify_server_cert(
+		&self,
+		_end_entity: &CertificateDer<'_>,
+		_intermediates: &[CertificateDer<'_>],
+		_server_name: &ServerName<'_>,
+		_ocsp_response: &[u8],
+		_now: UnixTime,
+	) -> Result<ServerCertVerified, rustls::Error> {
+		Ok(ServerCertVerified::assertion())
+	}
+
+	fn verify_tls12_signature(
+		&self,
+		_message: &[u8],
+		_cert: &CertificateDer<'_>,
+		_dss: &DigitallySignedStruct,
+	) -> Result<HandshakeSignatureValid, rustls::Error> {
+		Ok(HandshakeSignatureValid::assertion())
+	}
+
+	fn verify_tls13_signature(
+		&self,
+		_message: &[u8],
+		_cert: &CertificateDer<'_>,
+		_dss: &DigitallySignedStruct,
+	) -> Result<HandshakeSignatureValid, rustls::Error> {
+		Ok(HandshakeSignatureValid::assertion())
+	}
+
+	fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+		vec![
+			SignatureScheme::RSA_PKCS1_SHA256,
+			SignatureScheme::RSA_PKCS1_SHA384,
+			SignatureScheme::RSA_PKCS1_SHA512,
+			SignatureScheme::RSA_PSS_SHA256,
+			SignatureScheme::RSA_PSS_SHA384,
+			SignatureScheme::RSA_PSS_SHA512,
+			SignatureScheme::ECDSA_NISTP256_SHA256,
+			SignatureScheme::ECDSA_NISTP384_SHA384,
+			SignatureScheme::ECDSA_NISTP521_SHA512,
+			SignatureScheme::ED25519,
+			SignatureScheme::ED448,
+		]
+	}
+}
+
+fn load_certs(path: &str) -> Result<Vec<CertificateDer<'static>>, async_nats::Error> {
+	let mut reader = BufReader::new(std::fs::File::open(path)?);
+	Ok(rustls_pemfile::certs(&mut reader).collect::<Result<Vec<_>, _>>()?)
+}
+
+fn load_key(path: &str) -> Result<PrivateKeyDer<'static>, async_nats::Error> {
+	let mut reader = BufReader::new(std::fs::File::open(path)?);
+	rustls_pemfile::private_key(&mut reader)?
+		.ok_or_else(|| format!("no private key found in {}", path).into())
+}
+
+/// A `rustls` client config that skips server certificate verification
+/// entirely, for use with `--insecure-skip-verify`. `cert_key`, if given,
+/// is still presented for mTLS so that bypassing server verification
+/// doesn't silently drop client certificate authentication.
+pub fn insecure_client_config(cert_key: Option<(String, String)>)
+	-> Result<ClientConfig, async_nats::Error> {
+
+	let builder = ClientConfig::builder()
+		.dangerous()
+		.with_custom_certificate_verifier(Arc::new(NoCertVerification));
+
+	match cert_key {
+		Some((cert, key)) => Ok(builder.with_client_auth_cert(load_certs(&cert)?, load_key(&key)?)?),
+		None => Ok(builder.with_no_client_auth()),
+	}
+}